@@ -0,0 +1,109 @@
+use serde::ser::{Serialize, Serializer};
+use toml::value::Table;
+use toml::Value;
+
+use crate::cfg_expr::CfgExpr;
+use crate::errors::CargoPlayError;
+use crate::opt::RustEdition;
+use crate::steps::TargetDependencies;
+
+/// The generated `Cargo.toml` for a scaffolded script. Dependencies are parsed into tables up
+/// front so `Serialize` itself can't fail.
+pub struct CargoManifest {
+    name: String,
+    edition: RustEdition,
+    dependencies: Table,
+    target_dependencies: Vec<(String, Table)>,
+}
+
+impl CargoManifest {
+    pub fn new(
+        name: String,
+        dependencies: Vec<String>,
+        target_dependencies: Vec<TargetDependencies>,
+        edition: RustEdition,
+    ) -> Result<Self, CargoPlayError> {
+        let dependencies = parse_dependency_table(&dependencies)?;
+
+        let target_dependencies = target_dependencies
+            .into_iter()
+            .map(|target| {
+                // Reject malformed predicates early; Cargo does the actual gating.
+                CfgExpr::parse(&target.cfg).map_err(CargoPlayError::CfgParseError)?;
+                Ok((target.cfg, parse_dependency_table(&target.dependencies)?))
+            })
+            .collect::<Result<Vec<_>, CargoPlayError>>()?;
+
+        Ok(CargoManifest {
+            name,
+            edition,
+            dependencies,
+            target_dependencies,
+        })
+    }
+}
+
+fn parse_dependency_table(dependencies: &[String]) -> Result<Table, CargoPlayError> {
+    let mut table = Table::new();
+
+    for dependency in dependencies {
+        let parsed: Table = toml::from_str(dependency).map_err(CargoPlayError::from_serde)?;
+        table.extend(parsed);
+    }
+
+    Ok(table)
+}
+
+impl Serialize for CargoManifest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut package = Table::new();
+        package.insert("name".into(), Value::String(self.name.clone()));
+        package.insert("version".into(), Value::String("0.1.0".into()));
+        package.insert(
+            "edition".into(),
+            Value::String(self.edition.to_string()),
+        );
+
+        let mut root = Table::new();
+        root.insert("package".into(), Value::Table(package));
+        root.insert("dependencies".into(), Value::Table(self.dependencies.clone()));
+
+        if !self.target_dependencies.is_empty() {
+            let mut target = Table::new();
+
+            for (cfg, dependencies) in &self.target_dependencies {
+                let mut section = Table::new();
+                section.insert("dependencies".into(), Value::Table(dependencies.clone()));
+                target.insert(cfg.clone(), Value::Table(section));
+            }
+
+            root.insert("target".into(), Value::Table(target));
+        }
+
+        Value::Table(root).serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_target_dependencies_as_cfg_tables() {
+        let manifest = CargoManifest::new(
+            "cargo-play-test".into(),
+            vec![r#"log = "0.4""#.into()],
+            vec![TargetDependencies {
+                cfg: "cfg(unix)".into(),
+                dependencies: vec![r#"libc = "0.2""#.into()],
+            }],
+            RustEdition::default(),
+        )
+        .unwrap();
+
+        let toml = toml::to_string(&manifest).unwrap();
+
+        assert!(toml.contains("[dependencies]\nlog = \"0.4\""));
+        assert!(toml.contains("[target.\"cfg(unix)\".dependencies]\nlibc = \"0.2\""));
+    }
+}
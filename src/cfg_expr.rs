@@ -0,0 +1,286 @@
+use std::fmt;
+
+/// A parsed `cfg(...)` predicate, as used in target-specific `//#` dependency headers, e.g.
+/// `cfg(windows)` or `cfg(any(unix, target_os = "wasi"))`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfgExpr {
+    Ident(String),
+    KeyValue { key: String, value: String },
+    All(Vec<CfgExpr>),
+    Any(Vec<CfgExpr>),
+    Not(Box<CfgExpr>),
+}
+
+/// Describes the target a [`CfgExpr`] is evaluated against. Only covers the handful of
+/// `cfg`s that show up in real-world dependency gating; anything else evaluates to `false`.
+#[derive(Debug, Clone, Copy)]
+pub struct TargetInfo {
+    pub unix: bool,
+    pub windows: bool,
+    pub os: &'static str,
+}
+
+impl TargetInfo {
+    /// The target `cargo-play` itself is currently running on, used to warn the user when a
+    /// target-specific dependency group will be inert for their local `cargo run`.
+    pub fn current() -> Self {
+        TargetInfo {
+            unix: cfg!(unix),
+            windows: cfg!(windows),
+            os: std::env::consts::OS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CfgParseError(pub String);
+
+impl fmt::Display for CfgParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cfg predicate: {}", self.0)
+    }
+}
+
+impl std::error::Error for CfgParseError {}
+
+impl CfgExpr {
+    /// Parses a predicate such as `cfg(all(unix, target_os = "linux"))`. The leading `cfg(...)`
+    /// wrapper is optional, so `windows` and `cfg(windows)` both parse to `Ident("windows")`.
+    pub fn parse(input: &str) -> Result<Self, CfgParseError> {
+        let input = input.trim();
+        let input = input
+            .strip_prefix("cfg(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(input);
+
+        let mut parser = Parser {
+            tokens: tokenize(input),
+            pos: 0,
+        };
+        let expr = parser.parse_expr()?;
+
+        if parser.pos != parser.tokens.len() {
+            return Err(CfgParseError(input.to_owned()));
+        }
+
+        Ok(expr)
+    }
+
+    /// Evaluates this predicate against `target`.
+    pub fn eval(&self, target: &TargetInfo) -> bool {
+        match self {
+            CfgExpr::Ident(ident) => match ident.as_str() {
+                "unix" => target.unix,
+                "windows" => target.windows,
+                _ => false,
+            },
+            CfgExpr::KeyValue { key, value } => match key.as_str() {
+                "target_os" => value == target.os,
+                _ => false,
+            },
+            CfgExpr::All(exprs) => exprs.iter().all(|expr| expr.eval(target)),
+            CfgExpr::Any(exprs) => exprs.iter().any(|expr| expr.eval(target)),
+            CfgExpr::Not(expr) => !expr.eval(target),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Eq,
+    Comma,
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '=' => {
+                chars.next();
+                tokens.push(Token::Eq);
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+        }
+    }
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_expr(&mut self) -> Result<CfgExpr, CfgParseError> {
+        match self.next() {
+            Some(Token::Ident(ident)) if ident == "all" || ident == "any" => {
+                self.expect(Token::LParen)?;
+                let exprs = self.parse_expr_list()?;
+                self.expect(Token::RParen)?;
+                Ok(if ident == "all" {
+                    CfgExpr::All(exprs)
+                } else {
+                    CfgExpr::Any(exprs)
+                })
+            }
+            Some(Token::Ident(ident)) if ident == "not" => {
+                self.expect(Token::LParen)?;
+                let expr = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(CfgExpr::Not(Box::new(expr)))
+            }
+            Some(Token::Ident(ident)) => {
+                if self.peek() == Some(&Token::Eq) {
+                    self.next();
+                    match self.next() {
+                        Some(Token::Str(value)) => Ok(CfgExpr::KeyValue { key: ident, value }),
+                        other => Err(CfgParseError(format!("{:?}", other))),
+                    }
+                } else {
+                    Ok(CfgExpr::Ident(ident))
+                }
+            }
+            other => Err(CfgParseError(format!("{:?}", other))),
+        }
+    }
+
+    fn parse_expr_list(&mut self) -> Result<Vec<CfgExpr>, CfgParseError> {
+        let mut exprs = vec![self.parse_expr()?];
+
+        while self.peek() == Some(&Token::Comma) {
+            self.next();
+            exprs.push(self.parse_expr()?);
+        }
+
+        Ok(exprs)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), CfgParseError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(CfgParseError(format!("{:?}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(unix: bool, windows: bool, os: &'static str) -> TargetInfo {
+        TargetInfo { unix, windows, os }
+    }
+
+    #[test]
+    fn parses_bare_ident() {
+        assert_eq!(CfgExpr::parse("windows").unwrap(), CfgExpr::Ident("windows".into()));
+    }
+
+    #[test]
+    fn parses_wrapped_ident() {
+        assert_eq!(CfgExpr::parse("cfg(unix)").unwrap(), CfgExpr::Ident("unix".into()));
+    }
+
+    #[test]
+    fn parses_key_value() {
+        assert_eq!(
+            CfgExpr::parse("cfg(target_os = \"linux\")").unwrap(),
+            CfgExpr::KeyValue {
+                key: "target_os".into(),
+                value: "linux".into()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_all_any_not() {
+        let expr = CfgExpr::parse("cfg(all(unix, not(any(windows, target_os = \"wasi\"))))").unwrap();
+        assert_eq!(
+            expr,
+            CfgExpr::All(vec![
+                CfgExpr::Ident("unix".into()),
+                CfgExpr::Not(Box::new(CfgExpr::Any(vec![
+                    CfgExpr::Ident("windows".into()),
+                    CfgExpr::KeyValue {
+                        key: "target_os".into(),
+                        value: "wasi".into()
+                    }
+                ])))
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(CfgExpr::parse("cfg(all(unix,))").is_err());
+        assert!(CfgExpr::parse("cfg(unix").is_err());
+    }
+
+    #[test]
+    fn evaluates_against_target() {
+        let linux = target(true, false, "linux");
+        let windows = target(false, true, "windows");
+
+        assert!(CfgExpr::parse("unix").unwrap().eval(&linux));
+        assert!(!CfgExpr::parse("windows").unwrap().eval(&linux));
+        assert!(CfgExpr::parse("cfg(target_os = \"linux\")").unwrap().eval(&linux));
+        assert!(CfgExpr::parse("cfg(any(windows, target_os = \"linux\"))")
+            .unwrap()
+            .eval(&linux));
+        assert!(!CfgExpr::parse("cfg(all(unix, windows))").unwrap().eval(&windows));
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::cfg_expr::CfgParseError;
+
+/// The single error type returned by every step of the scaffolding pipeline.
+#[derive(Debug)]
+pub enum CargoPlayError {
+    Io(io::Error),
+    Serde(String),
+    DiffPathError(PathBuf),
+    PathExistError(PathBuf),
+    CopyError(PathBuf),
+    CfgParseError(CfgParseError),
+    SandboxError(String),
+    VirtualFilePathError(PathBuf),
+    DuplicateVirtualFileError(PathBuf),
+}
+
+impl CargoPlayError {
+    pub fn from_serde<E: fmt::Display>(err: E) -> Self {
+        CargoPlayError::Serde(err.to_string())
+    }
+}
+
+impl fmt::Display for CargoPlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CargoPlayError::Io(err) => write!(f, "{}", err),
+            CargoPlayError::Serde(err) => write!(f, "{}", err),
+            CargoPlayError::DiffPathError(path) => {
+                write!(f, "failed to compute a relative path for {:?}", path)
+            }
+            CargoPlayError::PathExistError(path) => write!(f, "{:?} already exists", path),
+            CargoPlayError::CopyError(path) => write!(f, "failed to copy {:?}", path),
+            CargoPlayError::CfgParseError(err) => write!(f, "{}", err),
+            CargoPlayError::SandboxError(message) => write!(f, "{}", message),
+            CargoPlayError::VirtualFilePathError(path) => {
+                write!(f, "virtual module path {:?} escapes the src directory", path)
+            }
+            CargoPlayError::DuplicateVirtualFileError(path) => {
+                write!(f, "virtual module path {:?} was declared more than once", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CargoPlayError {}
+
+impl From<io::Error> for CargoPlayError {
+    fn from(err: io::Error) -> Self {
+        CargoPlayError::Io(err)
+    }
+}
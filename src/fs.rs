@@ -0,0 +1,265 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Filesystem operations used by the scaffolding pipeline in `steps`, abstracted for testing.
+pub trait Fs {
+    /// Fails if `path` already exists, mirroring `std::fs::create_dir`.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    /// Lists the immediate children of `path`.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+    fn is_dir(&self, path: &Path) -> bool;
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+}
+
+/// `Fs` implementation backed by `std::fs`, used outside of tests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        std::fs::copy(from, to)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_dir_all(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// In-memory `Fs` implementation for unit tests.
+#[derive(Debug, Default)]
+pub struct FakeFs {
+    files: RefCell<HashMap<PathBuf, Vec<u8>>>,
+    dirs: RefCell<HashSet<PathBuf>>,
+}
+
+impl FakeFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seed the fake filesystem with an existing file, as if it had been
+    /// copied in from the real one.
+    pub fn with_file(self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        self.files.borrow_mut().insert(path.into(), contents.into());
+        self
+    }
+
+    /// Returns the contents written at `path`, if any.
+    pub fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        self.files.borrow().get(path).cloned()
+    }
+
+    /// Returns the paths of every file currently tracked, for asserting
+    /// exactly what scaffolding produced.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = self.files.borrow().keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+}
+
+impl Fs for FakeFs {
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        if self.dirs.borrow().contains(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("directory already exists in FakeFs: {:?}", path),
+            ));
+        }
+
+        self.dirs.borrow_mut().insert(path.to_path_buf());
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut dirs = self.dirs.borrow_mut();
+        let mut ancestor = PathBuf::new();
+        for component in path.components() {
+            ancestor.push(component);
+            dirs.insert(ancestor.clone());
+        }
+        Ok(())
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.files
+            .borrow_mut()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        let contents = self.files.borrow().get(from).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no such file in FakeFs: {:?}", from),
+            )
+        })?;
+        let len = contents.len() as u64;
+        self.files.borrow_mut().insert(to.to_path_buf(), contents);
+        Ok(len)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.files.borrow_mut().retain(|p, _| !p.starts_with(path));
+        Ok(())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(path.to_path_buf())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut children = Vec::new();
+
+        for known in self.files.borrow().keys().chain(self.dirs.borrow().iter()) {
+            if let Ok(rel) = known.strip_prefix(path) {
+                if let Some(first) = rel.components().next() {
+                    let child = path.join(first.as_os_str());
+                    if !children.contains(&child) {
+                        children.push(child);
+                    }
+                }
+            }
+        }
+
+        children.sort();
+        Ok(children)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.borrow().contains(path)
+            || self
+                .files
+                .borrow()
+                .keys()
+                .any(|file| file != path && file.starts_with(path))
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let contents = self.files.borrow().get(path).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no such file in FakeFs: {:?}", path))
+        })?;
+        String::from_utf8(contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_read() {
+        let fs = FakeFs::new();
+        fs.write(Path::new("src/main.rs"), b"fn main() {}").unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("src/main.rs")),
+            Some(b"fn main() {}".to_vec())
+        );
+        assert_eq!(fs.paths(), vec![PathBuf::from("src/main.rs")]);
+    }
+
+    #[test]
+    fn copy_carries_contents_to_new_path() {
+        let fs = FakeFs::new().with_file("foo.rs", "mod bar;");
+        fs.copy(Path::new("foo.rs"), Path::new("src/main.rs")).unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("src/main.rs")),
+            Some(b"mod bar;".to_vec())
+        );
+    }
+
+    #[test]
+    fn copy_missing_file_errors() {
+        let fs = FakeFs::new();
+        assert!(fs.copy(Path::new("missing.rs"), Path::new("src/main.rs")).is_err());
+    }
+
+    #[test]
+    fn read_to_string_returns_written_contents() {
+        let fs = FakeFs::new().with_file("main.rs", "fn main() {}");
+        assert_eq!(fs.read_to_string(Path::new("main.rs")).unwrap(), "fn main() {}");
+    }
+
+    #[test]
+    fn create_dir_fails_if_already_exists() {
+        let fs = FakeFs::new();
+        fs.create_dir(Path::new("/tmp/cargo-play")).unwrap();
+
+        assert!(fs.create_dir(Path::new("/tmp/cargo-play")).is_err());
+    }
+
+    #[test]
+    fn read_dir_lists_immediate_children_only() {
+        let fs = FakeFs::new()
+            .with_file("/project/src/main.rs", "")
+            .with_file("/project/target/debug/binary", "");
+
+        assert_eq!(
+            fs.read_dir(Path::new("/project")).unwrap(),
+            vec![
+                PathBuf::from("/project/src"),
+                PathBuf::from("/project/target"),
+            ]
+        );
+    }
+
+    #[test]
+    fn is_dir_true_for_paths_with_children() {
+        let fs = FakeFs::new().with_file("/project/src/main.rs", "");
+
+        assert!(fs.is_dir(Path::new("/project")));
+        assert!(fs.is_dir(Path::new("/project/src")));
+        assert!(!fs.is_dir(Path::new("/project/src/main.rs")));
+    }
+
+    #[test]
+    fn remove_dir_all_drops_nested_files() {
+        let fs = FakeFs::new()
+            .with_file("temp/src/main.rs", "fn main() {}")
+            .with_file("other/file.rs", "");
+        fs.remove_dir_all(Path::new("temp")).unwrap();
+
+        assert_eq!(fs.paths(), vec![PathBuf::from("other/file.rs")]);
+    }
+}
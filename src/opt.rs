@@ -0,0 +1,118 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use structopt::StructOpt;
+
+use crate::steps::{ContainerRuntime, SandboxOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RustEdition {
+    E2015,
+    #[default]
+    E2018,
+    E2021,
+}
+
+impl FromStr for RustEdition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2015" => Ok(RustEdition::E2015),
+            "2018" => Ok(RustEdition::E2018),
+            "2021" => Ok(RustEdition::E2021),
+            other => Err(format!("unsupported edition: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for RustEdition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RustEdition::E2015 => "2015",
+            RustEdition::E2018 => "2018",
+            RustEdition::E2021 => "2021",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Command-line options for `cargo-play`.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "cargo-play")]
+pub struct Opt {
+    /// Rust script(s) to run. The first becomes `src/main.rs`; the rest are copied in as
+    /// modules relative to it.
+    #[structopt(parse(from_os_str))]
+    pub src: Vec<PathBuf>,
+
+    #[structopt(long, default_value = "2018")]
+    pub edition: RustEdition,
+
+    #[structopt(long)]
+    pub release: bool,
+
+    /// Remove the scaffolded project for these scripts instead of running them.
+    #[structopt(long)]
+    pub clean: bool,
+
+    #[structopt(long)]
+    pub cargo_option: Option<String>,
+
+    #[structopt(long)]
+    pub toolchain: Option<String>,
+
+    /// Build and run inside a disposable Docker container instead of on the host.
+    #[structopt(long)]
+    pub sandbox: bool,
+
+    /// Container image to use with `--sandbox`; defaults to `rust:<toolchain>` when
+    /// `--toolchain` is given and to `rust:latest` otherwise. `--edition` does not affect
+    /// the default tag.
+    #[structopt(long)]
+    pub sandbox_image: Option<String>,
+
+    #[structopt(last = true)]
+    pub program_args: Vec<String>,
+}
+
+impl Opt {
+    /// Test helper for building an `Opt` from a fixed list of source paths.
+    pub fn with_files<I: Into<PathBuf>>(files: Vec<I>) -> Self {
+        Opt {
+            src: files.into_iter().map(Into::into).collect(),
+            edition: RustEdition::default(),
+            release: false,
+            clean: false,
+            cargo_option: None,
+            toolchain: None,
+            sandbox: false,
+            sandbox_image: None,
+            program_args: Vec::new(),
+        }
+    }
+
+    /// Name of the temporary project directory for this set of source paths, stable across
+    /// runs so re-running the same script reuses (or `--clean`s) the same scaffold.
+    pub fn temp_dirname(&self) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        self.src.hash(&mut hasher);
+        PathBuf::from(format!("cargo-play.{:x}", hasher.finish()))
+    }
+
+    /// Translates `--sandbox`/`--sandbox-image` into [`SandboxOptions`] for
+    /// [`crate::steps::run_cargo_build`], or `None` when `--sandbox` wasn't passed.
+    pub fn sandbox_options(&self) -> Option<SandboxOptions> {
+        if !self.sandbox {
+            return None;
+        }
+
+        Some(SandboxOptions {
+            runtime: ContainerRuntime::Docker,
+            image: self.sandbox_image.clone(),
+        })
+    }
+}
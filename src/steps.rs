@@ -2,14 +2,16 @@ use log::debug;
 use pathdiff::diff_paths;
 use std::env;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::iter::Iterator;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
 use std::vec::Vec;
 
 use crate::cargo::CargoManifest;
+use crate::cfg_expr::{CfgExpr, TargetInfo};
 use crate::errors::CargoPlayError;
+use crate::fs::Fs;
 use crate::opt::RustEdition;
 
 pub fn parse_inputs(inputs: &[PathBuf]) -> Result<Vec<String>, CargoPlayError> {
@@ -27,19 +29,91 @@ pub fn parse_inputs(inputs: &[PathBuf]) -> Result<Vec<String>, CargoPlayError> {
         .collect()
 }
 
-pub fn extract_headers(files: &[String]) -> Vec<String> {
-    files
-        .iter()
-        .map(|file: &String| -> Vec<String> {
-            file.lines()
-                .skip_while(|line| line.starts_with("#!") || line.is_empty())
-                .take_while(|line| line.starts_with("//#"))
-                .map(|line| line[3..].trim_start().into())
-                .filter(|s: &String| !s.is_empty())
-                .collect()
-        })
-        .flatten()
-        .collect()
+/// Dependencies collected from `//#` headers, split into the default `[dependencies]` table
+/// and any `[target.'cfg(...)'.dependencies]` tables requested by the script.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParsedHeaders {
+    pub dependencies: Vec<String>,
+    pub target_dependencies: Vec<TargetDependencies>,
+}
+
+/// One `[target.'cfg(...)'.dependencies]` table, keyed by the original predicate text (e.g.
+/// `cfg(windows)`) so it can be written back out verbatim into the generated `Cargo.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetDependencies {
+    pub cfg: String,
+    pub dependencies: Vec<String>,
+}
+
+impl ParsedHeaders {
+    fn push(&mut self, target: &Option<String>, dependency: String) {
+        match target {
+            Some(cfg) => match self.target_dependencies.iter_mut().find(|t| &t.cfg == cfg) {
+                Some(group) => group.dependencies.push(dependency),
+                None => self.target_dependencies.push(TargetDependencies {
+                    cfg: cfg.clone(),
+                    dependencies: vec![dependency],
+                }),
+            },
+            None => self.dependencies.push(dependency),
+        }
+    }
+}
+
+/// Parses a `[dependencies]` or `[target.'cfg(...)'.dependencies]` section header. Returns
+/// `None` if `line` isn't a section header at all, `Some(None)` for the default
+/// `[dependencies]` table, and `Some(Some(cfg))` for a target-specific one.
+fn parse_section_header(line: &str) -> Option<Option<String>> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+
+    if inner == "dependencies" {
+        return Some(None);
+    }
+
+    let cfg = inner
+        .strip_prefix("target.")?
+        .strip_suffix(".dependencies")?
+        .trim_matches('\'');
+
+    Some(Some(cfg.to_owned()))
+}
+
+pub fn extract_headers(files: &[String]) -> Result<ParsedHeaders, CargoPlayError> {
+    let mut headers = ParsedHeaders::default();
+    let target_info = TargetInfo::current();
+
+    for file in files {
+        let mut current_target: Option<String> = None;
+
+        for line in file
+            .lines()
+            .skip_while(|line| line.starts_with("#!") || line.is_empty())
+            .take_while(|line| line.starts_with("//#"))
+        {
+            let line = line[3..].trim_start();
+            if line.is_empty() {
+                continue;
+            }
+
+            match parse_section_header(line) {
+                Some(target) => {
+                    if let Some(cfg) = &target {
+                        let expr = CfgExpr::parse(cfg).map_err(CargoPlayError::CfgParseError)?;
+                        if !expr.eval(&target_info) {
+                            log::warn!(
+                                "Dependencies under [target.'{}'.dependencies] will not be built for the current target",
+                                cfg
+                            );
+                        }
+                    }
+                    current_target = target;
+                }
+                None => headers.push(&current_target, line.to_owned()),
+            }
+        }
+    }
+
+    Ok(headers)
 }
 
 pub fn temp_dir(name: PathBuf) -> PathBuf {
@@ -50,43 +124,52 @@ pub fn temp_dir(name: PathBuf) -> PathBuf {
 }
 
 /// This function ignores the error intentionally.
-pub fn rmtemp(temp: &PathBuf) {
+pub fn rmtemp(fs: &dyn Fs, temp: &Path) {
     debug!("Cleaning temporary folder at: {:?}", temp);
-    let _ = std::fs::remove_dir_all(temp);
+    let _ = fs.remove_dir_all(temp);
 }
 
-pub fn mktemp(temp: &PathBuf) {
+pub fn mktemp(fs: &dyn Fs, temp: &Path) {
     debug!("Creating temporary building folder at: {:?}", temp);
-    if std::fs::create_dir(temp).is_err() {
+    if fs.create_dir(temp).is_err() {
         debug!("Temporary directory already exists.");
     }
 }
 
 pub fn write_cargo_toml(
-    dir: &PathBuf,
+    fs: &dyn Fs,
+    dir: &Path,
     name: String,
-    dependencies: Vec<String>,
+    headers: ParsedHeaders,
     edition: RustEdition,
 ) -> Result<(), CargoPlayError> {
-    let manifest = CargoManifest::new(name, dependencies, edition)?;
-    let mut cargo = File::create(dir.join("Cargo.toml"))?;
+    let manifest = CargoManifest::new(
+        name,
+        headers.dependencies,
+        headers.target_dependencies,
+        edition,
+    )?;
+    let contents = toml::to_vec(&manifest).map_err(CargoPlayError::from_serde)?;
 
-    cargo.write_all(&toml::to_vec(&manifest).map_err(CargoPlayError::from_serde)?)?;
+    fs.write(&dir.join("Cargo.toml"), &contents)?;
 
     Ok(())
 }
 
 /// Copy all the passed in sources to the temporary directory. The first in the list will be
-/// treated as main.rs.
-pub fn copy_sources(temp: &PathBuf, sources: &[PathBuf]) -> Result<(), CargoPlayError> {
+/// treated as main.rs, split into further virtual modules wherever it contains `//@ file:`
+/// markers (see [`split_virtual_files`]); a file with no markers is just written out whole.
+pub fn copy_sources(fs: &dyn Fs, temp: &Path, sources: &[PathBuf]) -> Result<(), CargoPlayError> {
     let destination = temp.join("src");
-    std::fs::create_dir_all(&destination)?;
+    fs.create_dir_all(&destination)?;
 
     let mut files = sources.iter();
     let base = if let Some(first) = files.next() {
-        let dst = destination.join("main.rs");
-        debug!("Copying {:?} => {:?}", first, dst);
-        std::fs::copy(first, dst)?;
+        let contents = fs.read_to_string(first)?;
+
+        let virtual_files = split_virtual_files(&contents)?;
+        copy_virtual_sources(fs, temp, &virtual_files)?;
+
         first.parent()
     } else {
         None
@@ -101,11 +184,11 @@ pub fn copy_sources(temp: &PathBuf, sources: &[PathBuf]) -> Result<(), CargoPlay
 
                 // ensure the parent folder all exists
                 if let Some(parent) = dst.parent() {
-                    let _ = std::fs::create_dir_all(&parent);
+                    let _ = fs.create_dir_all(parent);
                 }
 
                 debug!("Copying {:?} => {:?}", file, dst);
-                std::fs::copy(file, dst).map(|_| ()).map_err(From::from)
+                fs.copy(file, &dst).map(|_| ()).map_err(From::from)
             })
             .collect::<Result<Vec<_>, _>>()?;
     }
@@ -113,13 +196,124 @@ pub fn copy_sources(temp: &PathBuf, sources: &[PathBuf]) -> Result<(), CargoPlay
     Ok(())
 }
 
+/// Marker line that starts a new virtual module within a single script file, e.g.
+/// `//@ file: foo/bar.rs`.
+const FILE_MARKER_PREFIX: &str = "//@ file:";
+
+/// Splits a single script's source into `main.rs` plus any additional virtual modules
+/// declared with `//@ file: <relative/path.rs>` markers. Everything before the first marker
+/// becomes `main.rs`; each marker afterwards starts a new buffer under its declared path,
+/// which is materialized later by [`copy_virtual_sources`]. A source with no markers at all
+/// is returned byte-for-byte, so scripts that never use the feature aren't re-encoded.
+pub fn split_virtual_files(source: &str) -> Result<Vec<(PathBuf, String)>, CargoPlayError> {
+    if !source.lines().any(|line| line.trim_start().starts_with(FILE_MARKER_PREFIX)) {
+        return Ok(vec![(PathBuf::from("main.rs"), source.to_owned())]);
+    }
+
+    let mut files: Vec<(PathBuf, String)> = vec![(PathBuf::from("main.rs"), String::new())];
+
+    for line in source.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix(FILE_MARKER_PREFIX) {
+            let path = PathBuf::from(rest.trim());
+
+            if path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+                return Err(CargoPlayError::VirtualFilePathError(path));
+            }
+
+            if files.iter().any(|(existing, _)| existing == &path) {
+                return Err(CargoPlayError::DuplicateVirtualFileError(path));
+            }
+
+            files.push((path, String::new()));
+            continue;
+        }
+
+        let buffer = &mut files.last_mut().unwrap().1;
+        buffer.push_str(line);
+        buffer.push('\n');
+    }
+
+    Ok(files)
+}
+
+/// Writes the virtual files produced by [`split_virtual_files`] under `temp/src`, creating
+/// parent directories for nested module paths as needed.
+pub fn copy_virtual_sources(
+    fs: &dyn Fs,
+    temp: &Path,
+    files: &[(PathBuf, String)],
+) -> Result<(), CargoPlayError> {
+    let destination = temp.join("src");
+    fs.create_dir_all(&destination)?;
+
+    for (path, contents) in files {
+        let dst = destination.join(path);
+
+        if let Some(parent) = dst.parent() {
+            fs.create_dir_all(parent)?;
+        }
+
+        debug!("Writing virtual module {:?} => {:?}", path, dst);
+        fs.write(&dst, contents.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Container runtime used by `--sandbox` to build and run untrusted scripts in isolation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    fn binary(self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+        }
+    }
+}
+
+/// Options for `--sandbox`, which builds and runs the generated project inside a disposable
+/// container instead of on the host.
+#[derive(Debug, Clone)]
+pub struct SandboxOptions {
+    pub runtime: ContainerRuntime,
+    pub image: Option<String>,
+}
+
+impl SandboxOptions {
+    /// The image to run: the user-provided `--sandbox-image`, or an official `rust` tag
+    /// matching the selected toolchain.
+    fn image(&self, toolchain: &Option<String>) -> String {
+        self.image.clone().unwrap_or_else(|| match toolchain {
+            Some(toolchain) => format!("rust:{}", toolchain),
+            None => String::from("rust:latest"),
+        })
+    }
+}
+
 pub fn run_cargo_build(
     toolchain: Option<String>,
-    project: &PathBuf,
+    project: &Path,
     release: bool,
     cargo_option: Option<String>,
     program_args: &[String],
+    sandbox: Option<SandboxOptions>,
 ) -> Result<ExitStatus, CargoPlayError> {
+    if let Some(sandbox) = sandbox {
+        return run_cargo_build_sandboxed(
+            &sandbox,
+            toolchain,
+            project,
+            release,
+            cargo_option,
+            program_args,
+        );
+    }
+
     let mut cargo = Command::new("cargo");
 
     if let Some(toolchain) = toolchain {
@@ -149,30 +343,381 @@ pub fn run_cargo_build(
         .map_err(From::from)
 }
 
+/// Runs `cargo run` for `project` inside a disposable container, bind-mounting the project
+/// read-only and the `target/` directory read-write so build artifacts don't leak onto the
+/// host outside of it. The container is torn down on exit via `--rm`.
+fn run_cargo_build_sandboxed(
+    sandbox: &SandboxOptions,
+    toolchain: Option<String>,
+    project: &Path,
+    release: bool,
+    cargo_option: Option<String>,
+    program_args: &[String],
+) -> Result<ExitStatus, CargoPlayError> {
+    let binary = sandbox.runtime.binary();
+    let image = sandbox.image(&toolchain);
+    let target = project.join("target");
+
+    let mut container = Command::new(binary);
+    container
+        .arg("run")
+        .arg("--rm")
+        .arg("-v")
+        .arg(format!("{}:/project:ro", project.display()))
+        .arg("-v")
+        .arg(format!("{}:/project/target:rw", target.display()))
+        .arg("-w")
+        .arg("/project")
+        .arg(&image)
+        .arg("cargo")
+        .arg("run")
+        .arg("--manifest-path")
+        .arg("/project/Cargo.toml");
+
+    if let Some(cargo_option) = cargo_option {
+        // FIXME: proper escaping
+        container.args(cargo_option.split_ascii_whitespace());
+    }
+
+    if release {
+        container.arg("--release");
+    }
+
+    container
+        .arg("--")
+        .args(program_args)
+        .stderr(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .status()
+        .map_err(|e| CargoPlayError::SandboxError(format!("failed to launch {}: {}", binary, e)))
+}
+
+/// Recursively copies the scaffolded project from `from` to `to`, skipping `target/` unless
+/// `include_target` is set.
 pub fn copy_project<T: AsRef<Path>, U: AsRef<Path>>(
+    fs: &dyn Fs,
     from: T,
     to: U,
-) -> Result<ExitStatus, CargoPlayError> {
+    include_target: bool,
+) -> Result<(), CargoPlayError> {
+    let from = from.as_ref();
     let to = to.as_ref();
 
-    if to.is_dir() {
+    if fs.is_dir(to) {
         return Err(CargoPlayError::PathExistError(to.to_path_buf()));
     }
 
-    Command::new("cp")
-        .arg("-R")
-        .arg(from.as_ref())
-        .arg(&to)
-        .stderr(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .status()
-        .map(|x| {
-            // At this point we are certain the `to` path exists
-            println!(
-                "Generated project at {}",
-                to.canonicalize().unwrap().display()
-            );
-            x
-        })
-        .map_err(From::from)
+    copy_dir_all(fs, from, to, from, include_target)?;
+
+    // At this point we are certain the `to` path exists.
+    println!(
+        "Generated project at {}",
+        fs.canonicalize(to)?.display()
+    );
+
+    Ok(())
+}
+
+/// Walks `src` and recreates it under `dst`, skipping `root/target` unless `include_target`
+/// is set. Stops at the first path that fails to copy.
+fn copy_dir_all(
+    fs: &dyn Fs,
+    src: &Path,
+    dst: &Path,
+    root: &Path,
+    include_target: bool,
+) -> Result<(), CargoPlayError> {
+    fs.create_dir_all(dst)
+        .map_err(|_| CargoPlayError::CopyError(dst.to_path_buf()))?;
+
+    let entries = fs
+        .read_dir(src)
+        .map_err(|_| CargoPlayError::CopyError(src.to_path_buf()))?;
+
+    for path in entries {
+        if !include_target && src == root && path.file_name() == Some("target".as_ref()) {
+            debug!("Skipping {:?}", path);
+            continue;
+        }
+
+        let part =
+            diff_paths(&path, src).ok_or_else(|| CargoPlayError::DiffPathError(path.clone()))?;
+        let dst = dst.join(part);
+
+        if fs.is_dir(&path) {
+            copy_dir_all(fs, &path, &dst, root, include_target)?;
+        } else {
+            debug!("Copying {:?} => {:?}", path, dst);
+            fs.copy(&path, &dst)
+                .map_err(|_| CargoPlayError::CopyError(path))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::FakeFs;
+    use std::io;
+
+    #[test]
+    fn split_virtual_files_separates_main_and_named_modules() {
+        let source = "fn main() {}\n\
+                       //@ file: foo.rs\n\
+                       pub fn foo() {}\n\
+                       //@ file: bar/baz.rs\n\
+                       pub fn baz() {}\n";
+
+        let files = split_virtual_files(source).unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                (PathBuf::from("main.rs"), "fn main() {}\n".to_owned()),
+                (PathBuf::from("foo.rs"), "pub fn foo() {}\n".to_owned()),
+                (PathBuf::from("bar/baz.rs"), "pub fn baz() {}\n".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_virtual_files_rejects_duplicate_paths() {
+        let source = "//@ file: foo.rs\na\n//@ file: foo.rs\nb\n";
+        assert!(split_virtual_files(source).is_err());
+    }
+
+    #[test]
+    fn split_virtual_files_rejects_paths_escaping_src() {
+        let source = "//@ file: ../escape.rs\na\n";
+        assert!(split_virtual_files(source).is_err());
+    }
+
+    #[test]
+    fn copy_virtual_sources_writes_nested_modules_under_src() {
+        let fs = FakeFs::new();
+        let temp = PathBuf::from("/tmp/cargo-play");
+        let files = vec![
+            (PathBuf::from("main.rs"), "mod foo;".to_owned()),
+            (PathBuf::from("foo.rs"), "pub fn foo() {}".to_owned()),
+        ];
+
+        copy_virtual_sources(&fs, &temp, &files).unwrap();
+
+        assert_eq!(
+            fs.read(&temp.join("src/main.rs")),
+            Some(b"mod foo;".to_vec())
+        );
+        assert_eq!(
+            fs.read(&temp.join("src/foo.rs")),
+            Some(b"pub fn foo() {}".to_vec())
+        );
+    }
+
+    #[test]
+    fn sandbox_image_defaults_to_toolchain_matching_rust_tag() {
+        let sandbox = SandboxOptions {
+            runtime: ContainerRuntime::Docker,
+            image: None,
+        };
+
+        assert_eq!(sandbox.image(&None), "rust:latest");
+        assert_eq!(sandbox.image(&Some("1.70".into())), "rust:1.70");
+    }
+
+    #[test]
+    fn sandbox_image_prefers_explicit_override() {
+        let sandbox = SandboxOptions {
+            runtime: ContainerRuntime::Podman,
+            image: Some("my-registry/rust:custom".into()),
+        };
+
+        assert_eq!(
+            sandbox.image(&Some("1.70".into())),
+            "my-registry/rust:custom"
+        );
+    }
+
+    #[test]
+    fn extract_headers_splits_default_and_target_tables() {
+        let file = String::from(
+            "//# serde = \"1\"\n\
+             //# [target.'cfg(windows)'.dependencies]\n\
+             //# winapi = \"0.3\"\n\
+             //# [target.'cfg(unix)'.dependencies]\n\
+             //# libc = \"0.2\"\n\
+             //# [dependencies]\n\
+             //# rand = \"0.8\"\n\
+             fn main() {}\n",
+        );
+
+        let headers = extract_headers(&[file]).unwrap();
+
+        assert_eq!(headers.dependencies, vec!["serde = \"1\"", "rand = \"0.8\""]);
+        assert_eq!(
+            headers.target_dependencies,
+            vec![
+                TargetDependencies {
+                    cfg: "cfg(windows)".into(),
+                    dependencies: vec!["winapi = \"0.3\"".into()],
+                },
+                TargetDependencies {
+                    cfg: "cfg(unix)".into(),
+                    dependencies: vec!["libc = \"0.2\"".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_headers_rejects_invalid_cfg() {
+        let file = String::from("//# [target.'cfg(unix,)'.dependencies]\n//# libc = \"0.2\"\n");
+
+        assert!(extract_headers(&[file]).is_err());
+    }
+
+    #[test]
+    fn copy_project_skips_target_by_default() {
+        let fs = FakeFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/target/debug/binary", "");
+
+        copy_project(&fs, "/project", "/dest", false).unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("/dest/src/main.rs")),
+            Some(b"fn main() {}".to_vec())
+        );
+        assert!(fs
+            .paths()
+            .iter()
+            .all(|path| !path.starts_with("/dest/target")));
+    }
+
+    #[test]
+    fn copy_project_includes_target_when_requested() {
+        let fs = FakeFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}")
+            .with_file("/project/target/debug/binary", "bin");
+
+        copy_project(&fs, "/project", "/dest", true).unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("/dest/target/debug/binary")),
+            Some(b"bin".to_vec())
+        );
+    }
+
+    #[test]
+    fn copy_project_errors_when_destination_already_exists() {
+        let fs = FakeFs::new().with_file("/dest/src/main.rs", "");
+
+        assert!(matches!(
+            copy_project(&fs, "/project", "/dest", false),
+            Err(CargoPlayError::PathExistError(path)) if path == Path::new("/dest")
+        ));
+    }
+
+    /// `Fs` whose `copy` always fails, so `copy_dir_all`'s `CopyError` mapping can be
+    /// exercised without relying on the real filesystem.
+    struct FailingCopyFs(FakeFs);
+
+    impl Fs for FailingCopyFs {
+        fn create_dir(&self, path: &Path) -> io::Result<()> {
+            self.0.create_dir(path)
+        }
+        fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+            self.0.create_dir_all(path)
+        }
+        fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+            self.0.write(path, contents)
+        }
+        fn copy(&self, _from: &Path, _to: &Path) -> io::Result<u64> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "copy denied"))
+        }
+        fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+            self.0.remove_dir_all(path)
+        }
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.0.canonicalize(path)
+        }
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+            self.0.read_dir(path)
+        }
+        fn is_dir(&self, path: &Path) -> bool {
+            self.0.is_dir(path)
+        }
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            self.0.read_to_string(path)
+        }
+    }
+
+    #[test]
+    fn copy_project_wraps_copy_failures_in_copy_error() {
+        let fs = FailingCopyFs(FakeFs::new().with_file("/project/src/main.rs", "fn main() {}"));
+
+        assert!(matches!(
+            copy_project(&fs, "/project", "/dest", false),
+            Err(CargoPlayError::CopyError(_))
+        ));
+    }
+
+    #[test]
+    fn copy_sources_writes_main_and_relative_modules() {
+        let fs = FakeFs::new()
+            .with_file("/project/src/main.rs", "mod foo;\n")
+            .with_file("/project/src/foo.rs", "pub fn foo() {}");
+        let temp = PathBuf::from("/tmp/cargo-play");
+
+        copy_sources(
+            &fs,
+            &temp,
+            &[
+                PathBuf::from("/project/src/main.rs"),
+                PathBuf::from("/project/src/foo.rs"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("/tmp/cargo-play/src/main.rs")),
+            Some(b"mod foo;\n".to_vec())
+        );
+        assert_eq!(
+            fs.read(Path::new("/tmp/cargo-play/src/foo.rs")),
+            Some(b"pub fn foo() {}".to_vec())
+        );
+    }
+
+    #[test]
+    fn copy_sources_splits_file_markers_in_main_script() {
+        let fs = FakeFs::new()
+            .with_file("/project/src/main.rs", "fn main() {}\n//@ file: foo.rs\npub fn foo() {}\n");
+        let temp = PathBuf::from("/tmp/cargo-play");
+
+        copy_sources(&fs, &temp, &[PathBuf::from("/project/src/main.rs")]).unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("/tmp/cargo-play/src/main.rs")),
+            Some(b"fn main() {}\n".to_vec())
+        );
+        assert_eq!(
+            fs.read(Path::new("/tmp/cargo-play/src/foo.rs")),
+            Some(b"pub fn foo() {}\n".to_vec())
+        );
+    }
+
+    #[test]
+    fn copy_sources_preserves_main_byte_for_byte_without_markers() {
+        let fs = FakeFs::new().with_file("/project/src/main.rs", "fn main() {}\r\n// no trailing newline");
+        let temp = PathBuf::from("/tmp/cargo-play");
+
+        copy_sources(&fs, &temp, &[PathBuf::from("/project/src/main.rs")]).unwrap();
+
+        assert_eq!(
+            fs.read(Path::new("/tmp/cargo-play/src/main.rs")),
+            Some(b"fn main() {}\r\n// no trailing newline".to_vec())
+        );
+    }
 }